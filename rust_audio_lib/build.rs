@@ -1,6 +1,7 @@
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 fn main() {
     println!("cargo:rustc-link-lib=framework=CoreAudio");
+    println!("cargo:rustc-link-lib=framework=CoreFoundation");
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "ios")))]
@@ -2,12 +2,13 @@ mod sys; // Module contains types and functions of the external libraries
 
 pub mod utils {
     use super::sys; // Bring `sys` module into scope
-    use std::mem;   // For mem::uninitialized(), mem::size_of_val()
+    use std::ffi::CStr; // For CStr::from_ptr()
+    use std::mem;   // For mem::MaybeUninit, mem::size_of()
     use std::os::raw::c_void;
     use std::ptr; // For ptr::null()
 
     #[repr(C)]
-    #[derive(PartialEq)] // Enable comparison
+    #[derive(Clone, Copy, PartialEq)] // Enable comparison
     pub enum Scope {
         Input,
         Output,
@@ -19,19 +20,48 @@ pub mod utils {
         Ok,
         NoDevice,
         InvalidParameters,
+        NotRunning,
+        UnknownProperty,
+        BadDevice,
+        Unknown(sys::OSStatus),
     }
 
     impl From<sys::OSStatus> for Error {
         fn from(status: sys::OSStatus) -> Error {
             match status {
                 sys::kAudioHardwareBadObjectError => Error::InvalidParameters,
-                s => panic!("Unknown error status: {}", s),
+                sys::kAudioHardwareNotRunningError => Error::NotRunning,
+                sys::kAudioHardwareUnknownPropertyError => Error::UnknownProperty,
+                sys::kAudioHardwareBadDeviceError => Error::BadDevice,
+                s => Error::Unknown(s),
             }
         }
     }
 
     pub type DeviceId = i32;
 
+    /// A typed handle to a CoreAudio object, so callers can work with audio
+    /// devices without threading raw ids through every call.
+    pub struct AudioObject(DeviceId);
+
+    impl AudioObject {
+        pub fn default(scope: &Scope) -> Result<AudioObject, Error> {
+            Ok(AudioObject(get_default_device_id(scope)?))
+        }
+
+        pub fn name(&self) -> Result<String, Error> {
+            get_device_name(self.0)
+        }
+
+        pub fn channel_count(&self, scope: &Scope) -> Result<u32, Error> {
+            get_channel_count(self.0, scope)
+        }
+
+        pub fn sample_rates(&self, scope: &Scope) -> Result<Vec<(f64, f64)>, Error> {
+            get_available_sample_rates(self.0, scope)
+        }
+    }
+
     const DEFAULT_INPUT_DEVICE_PROPERTY_ADDRESS: sys::AudioObjectPropertyAddress =
         sys::AudioObjectPropertyAddress {
             mSelector: sys::kAudioHardwarePropertyDefaultInputDevice,
@@ -46,6 +76,13 @@ pub mod utils {
             mElement: sys::kAudioObjectPropertyElementMaster,
         };
 
+    const DEVICES_PROPERTY_ADDRESS: sys::AudioObjectPropertyAddress =
+        sys::AudioObjectPropertyAddress {
+            mSelector: sys::kAudioHardwarePropertyDevices,
+            mScope: sys::kAudioObjectPropertyScopeGlobal,
+            mElement: sys::kAudioObjectPropertyElementMaster,
+        };
+
     pub fn get_default_device_id(scope: &Scope) -> Result<DeviceId, Error> {
         let address: &sys::AudioObjectPropertyAddress = if scope == &Scope::Input {
             &DEFAULT_INPUT_DEVICE_PROPERTY_ADDRESS
@@ -60,21 +97,235 @@ pub mod utils {
         }
     }
 
+    fn default_device_property_address(scope: &Scope) -> &'static sys::AudioObjectPropertyAddress {
+        if scope == &Scope::Input {
+            &DEFAULT_INPUT_DEVICE_PROPERTY_ADDRESS
+        } else {
+            &DEFAULT_OUTPUT_DEVICE_PROPERTY_ADDRESS
+        }
+    }
+
+    struct ListenerContext {
+        scope: Scope,
+        callback: extern "C" fn(DeviceId, *mut c_void),
+        user_data: *mut c_void,
+    }
+
+    /// Handle to a default-device change subscription. Unregisters the
+    /// underlying CoreAudio listener when dropped.
+    pub struct ListenerHandle {
+        address: sys::AudioObjectPropertyAddress,
+        context: *mut ListenerContext,
+    }
+
+    // CoreAudio invokes the listener proc from its own internal thread, so the
+    // handle (and the context it frees on drop) must be safe to move there.
+    unsafe impl Send for ListenerHandle {}
+
+    impl Drop for ListenerHandle {
+        fn drop(&mut self) {
+            unsafe {
+                sys::AudioObjectRemovePropertyListener(
+                    sys::kAudioObjectSystemObject,
+                    &self.address,
+                    default_device_listener_trampoline,
+                    self.context as *mut c_void,
+                );
+                drop(Box::from_raw(self.context));
+            }
+        }
+    }
+
+    pub fn add_default_device_listener(
+        scope: &Scope,
+        callback: extern "C" fn(DeviceId, *mut c_void),
+        user_data: *mut c_void,
+    ) -> Result<ListenerHandle, Error> {
+        let address = *default_device_property_address(scope);
+        let context = Box::into_raw(Box::new(ListenerContext {
+            scope: *scope,
+            callback,
+            user_data,
+        }));
+        let status = unsafe {
+            sys::AudioObjectAddPropertyListener(
+                sys::kAudioObjectSystemObject,
+                &address,
+                default_device_listener_trampoline,
+                context as *mut c_void,
+            )
+        };
+        if let Err(error) = convert_to_result(status) {
+            unsafe {
+                drop(Box::from_raw(context));
+            }
+            return Err(error);
+        }
+        Ok(ListenerHandle { address, context })
+    }
+
+    extern "C" fn default_device_listener_trampoline(
+        _in_object_id: sys::AudioObjectID,
+        _in_number_addresses: u32,
+        _in_addresses: *const sys::AudioObjectPropertyAddress,
+        in_client_data: *mut c_void,
+    ) -> sys::OSStatus {
+        let context = unsafe { &*(in_client_data as *const ListenerContext) };
+        if let Ok(device_id) = get_default_device_id(&context.scope) {
+            (context.callback)(device_id, context.user_data);
+        }
+        sys::kAudioHardwareNoError
+    }
+
+    pub fn get_all_device_ids() -> Result<Vec<DeviceId>, Error> {
+        let ids = get_property_array::<sys::AudioObjectID>(
+            sys::kAudioObjectSystemObject,
+            &DEVICES_PROPERTY_ADDRESS,
+        )?;
+        Ok(ids.into_iter().map(to_device_id).collect())
+    }
+
+    pub fn get_device_ids(scope: &Scope) -> Result<Vec<DeviceId>, Error> {
+        let ids = get_all_device_ids()?;
+        let mut in_scope = Vec::new();
+        for id in ids {
+            // A device that errors while being probed (e.g. it doesn't
+            // support this scope) is simply not part of it, rather than a
+            // reason to abort the whole enumeration.
+            if get_channel_count(id, scope).unwrap_or(0) > 0 {
+                in_scope.push(id);
+            }
+        }
+        Ok(in_scope)
+    }
+
+    pub fn get_available_sample_rates(
+        id: DeviceId,
+        scope: &Scope,
+    ) -> Result<Vec<(f64, f64)>, Error> {
+        let address = sys::AudioObjectPropertyAddress {
+            mSelector: sys::kAudioDevicePropertyAvailableNominalSampleRates,
+            mScope: scope_selector(scope),
+            mElement: sys::kAudioObjectPropertyElementMaster,
+        };
+        let ranges = get_property_array::<sys::AudioValueRange>(to_audio_object_id(id), &address)?;
+        Ok(ranges
+            .into_iter()
+            .map(|range| (range.mMinimum, range.mMaximum))
+            .collect())
+    }
+
+    pub fn get_channel_count(id: DeviceId, scope: &Scope) -> Result<u32, Error> {
+        let address = stream_configuration_address(scope);
+        let buffer_list = get_property_array::<u8>(to_audio_object_id(id), &address)?;
+
+        // `buffer_list` is a raw `Vec<u8>`, not guaranteed to be aligned for
+        // `AudioBufferList`, and may be shorter than a full struct (e.g. a
+        // device with no channels in this scope reports an empty property).
+        // Read fields with unaligned, bounds-checked byte offsets instead of
+        // dereferencing it as an `AudioBufferList`.
+        if buffer_list.len() < mem::size_of::<u32>() {
+            return Ok(0);
+        }
+        let number_buffers =
+            unsafe { ptr::read_unaligned(buffer_list.as_ptr() as *const u32) } as usize;
+
+        let buffers_offset = mem::size_of::<sys::AudioBufferList>() - mem::size_of::<sys::AudioBuffer>();
+        let buffer_size = mem::size_of::<sys::AudioBuffer>();
+        let available_buffers = buffer_list
+            .len()
+            .saturating_sub(buffers_offset)
+            / buffer_size;
+        let count = number_buffers.min(available_buffers);
+
+        let mut channels = 0;
+        for i in 0..count {
+            let offset = buffers_offset + i * buffer_size;
+            channels +=
+                unsafe { ptr::read_unaligned(buffer_list.as_ptr().add(offset) as *const u32) };
+        }
+        Ok(channels)
+    }
+
+    pub fn get_device_name(id: DeviceId) -> Result<String, Error> {
+        let address = sys::AudioObjectPropertyAddress {
+            mSelector: sys::kAudioDevicePropertyDeviceNameCFString,
+            mScope: sys::kAudioObjectPropertyScopeGlobal,
+            mElement: sys::kAudioObjectPropertyElementMaster,
+        };
+        let name_ref =
+            get_property_data::<sys::CFStringRef>(to_audio_object_id(id), &address)?;
+        let name = cfstring_ref_to_string(name_ref);
+        unsafe {
+            sys::CFRelease(name_ref as *const c_void);
+        }
+        Ok(name)
+    }
+
+    fn cfstring_ref_to_string(string_ref: sys::CFStringRef) -> String {
+        // Fast path: CFStringGetCStringPtr returns null when the string isn't
+        // already stored internally as UTF-8, so fall back to a copy below.
+        let c_str_ptr = unsafe { sys::CFStringGetCStringPtr(string_ref, sys::kCFStringEncodingUTF8) };
+        if !c_str_ptr.is_null() {
+            let c_str = unsafe { CStr::from_ptr(c_str_ptr) };
+            return c_str.to_string_lossy().into_owned();
+        }
+
+        let mut buffer = [0i8; 256];
+        let success = unsafe {
+            sys::CFStringGetCString(
+                string_ref,
+                buffer.as_mut_ptr(),
+                buffer.len() as isize,
+                sys::kCFStringEncodingUTF8,
+            )
+        };
+        if success == 0 {
+            return String::new();
+        }
+        let c_str = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        c_str.to_string_lossy().into_owned()
+    }
+
+    fn stream_configuration_address(scope: &Scope) -> sys::AudioObjectPropertyAddress {
+        sys::AudioObjectPropertyAddress {
+            mSelector: sys::kAudioDevicePropertyStreamConfiguration,
+            mScope: scope_selector(scope),
+            mElement: sys::kAudioObjectPropertyElementMaster,
+        }
+    }
+
+    fn scope_selector(scope: &Scope) -> u32 {
+        if scope == &Scope::Input {
+            sys::kAudioObjectPropertyScopeInput
+        } else {
+            sys::kAudioObjectPropertyScopeOutput
+        }
+    }
+
     fn to_device_id(id: sys::AudioObjectID) -> DeviceId {
         id as DeviceId
     }
 
+    fn to_audio_object_id(id: DeviceId) -> sys::AudioObjectID {
+        id as sys::AudioObjectID
+    }
+
     fn get_property_data<T>(
         id: sys::AudioObjectID,
         address: &sys::AudioObjectPropertyAddress,
     ) -> Result<T, Error> {
         assert!(id != sys::kAudioObjectUnknown, "Bad AudioObjectID!");
-        // Use `mem::uninitialized()` to bypasses memory-initialization checks
-        let mut data: T = unsafe { mem::uninitialized() };
-        let mut size = mem::size_of_val(&data);
-        let status = audio_object_get_property_data(id, address, &mut size, &mut data);
+        let mut data: mem::MaybeUninit<T> = mem::MaybeUninit::uninit();
+        let mut size = mem::size_of::<T>();
+        let status = audio_object_get_property_data(id, address, &mut size, data.as_mut_ptr());
         convert_to_result(status)?;
-        Ok(data)
+        if size != mem::size_of::<T>() {
+            return Err(Error::InvalidParameters);
+        }
+        // Safe: CoreAudio reported `kAudioHardwareNoError` and wrote exactly
+        // `size_of::<T>()` bytes, so `data` is fully initialized.
+        Ok(unsafe { data.assume_init() })
     }
 
     fn audio_object_get_property_data<T>(
@@ -95,6 +346,49 @@ pub mod utils {
         }
     }
 
+    fn get_property_array<T>(
+        id: sys::AudioObjectID,
+        address: &sys::AudioObjectPropertyAddress,
+    ) -> Result<Vec<T>, Error> {
+        assert!(id != sys::kAudioObjectUnknown, "Bad AudioObjectID!");
+        let mut size: usize = 0;
+        let status = audio_object_get_property_data_size(id, address, &mut size);
+        convert_to_result(status)?;
+
+        let count = size / mem::size_of::<T>();
+        let mut data: Vec<T> = Vec::with_capacity(count);
+        let mut size = size;
+        let status = audio_object_get_property_data(id, address, &mut size, data.as_mut_ptr());
+        convert_to_result(status)?;
+        // The second call can write fewer bytes than the size query reported
+        // (e.g. the array shrank in between). Any `size` other than exactly
+        // `count` elements is rejected as an error, so uninitialized elements
+        // of `data` are never marked as initialized.
+        if size != count * mem::size_of::<T>() {
+            return Err(Error::InvalidParameters);
+        }
+        unsafe {
+            data.set_len(count);
+        }
+        Ok(data)
+    }
+
+    fn audio_object_get_property_data_size(
+        id: sys::AudioObjectID,
+        address: &sys::AudioObjectPropertyAddress,
+        size: *mut usize,
+    ) -> sys::OSStatus {
+        unsafe {
+            sys::AudioObjectGetPropertyDataSize(
+                id,
+                address, // as `*const sys::AudioObjectPropertyAddress` automatically
+                0,
+                ptr::null(),
+                size as *mut u32, // Cast raw usize pointer to raw u32 pointer
+            )
+        }
+    }
+
     fn convert_to_result(status: sys::OSStatus) -> Result<(), Error> {
         match status {
             sys::kAudioHardwareNoError => Ok(()),
@@ -147,6 +441,57 @@ pub mod utils {
                 kAudioObjectUnknown
             );
         }
+
+        #[test] // Built only within `cargo test`.
+        fn test_get_all_device_ids() {
+            // The system always has at least the default input/output devices.
+            assert!(!get_all_device_ids().unwrap().is_empty());
+        }
+
+        #[test] // Built only within `cargo test`.
+        fn test_get_device_ids() {
+            assert!(get_device_ids(&Scope::Input).is_ok());
+            assert!(get_device_ids(&Scope::Output).is_ok());
+        }
+
+        #[test] // Built only within `cargo test`.
+        fn test_get_device_name() {
+            let id = get_default_device_id(&Scope::Output).unwrap();
+            assert!(!get_device_name(id).unwrap().is_empty());
+        }
+
+        #[test] // Built only within `cargo test`.
+        fn test_get_channel_count() {
+            let id = get_default_device_id(&Scope::Output).unwrap();
+            assert!(get_channel_count(id, &Scope::Output).unwrap() > 0);
+        }
+
+        #[test] // Built only within `cargo test`.
+        fn test_get_available_sample_rates() {
+            let id = get_default_device_id(&Scope::Output).unwrap();
+            assert!(!get_available_sample_rates(id, &Scope::Output)
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test] // Built only within `cargo test`.
+        fn test_audio_object_default() {
+            let object = AudioObject::default(&Scope::Output).unwrap();
+            assert!(!object.name().unwrap().is_empty());
+            assert!(object.channel_count(&Scope::Output).unwrap() > 0);
+            assert!(!object.sample_rates(&Scope::Output).unwrap().is_empty());
+        }
+
+        extern "C" fn noop_listener(_id: DeviceId, _user_data: *mut c_void) {}
+
+        #[test] // Built only within `cargo test`.
+        fn test_add_default_device_listener() {
+            // Smoke test: registering (and dropping) a listener should
+            // succeed without erroring or panicking.
+            let handle =
+                add_default_device_listener(&Scope::Output, noop_listener, ptr::null_mut());
+            assert!(handle.is_ok());
+        }
     }
 }
 
@@ -166,3 +511,26 @@ pub extern "C" fn get_default_device_id(
         Err(error) => error,
     }
 }
+
+#[no_mangle] // Tell the Rust compiler not to mangle the name of this function.
+pub extern "C" fn get_device_ids(
+    scope: utils::Scope,
+    ids: *mut utils::DeviceId,
+    count: *mut u32,
+) -> utils::Error {
+    if ids.is_null() || count.is_null() {
+        return utils::Error::InvalidParameters;
+    }
+    let capacity = unsafe { *count } as usize;
+    match utils::get_device_ids(&scope) {
+        Ok(device_ids) => {
+            let written = std::cmp::min(capacity, device_ids.len());
+            for (i, device_id) in device_ids.into_iter().take(written).enumerate() {
+                unsafe { *ids.add(i) = device_id };
+            }
+            unsafe { *count = written as u32 };
+            utils::Error::Ok
+        }
+        Err(error) => error,
+    }
+}
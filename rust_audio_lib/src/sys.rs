@@ -1,6 +1,6 @@
 #![allow(non_snake_case, non_upper_case_globals)]
 
-use std::mem; // For mem::uninitialized(), mem::size_of_val()
+use std::mem; // For mem::MaybeUninit, mem::size_of()
 use std::os::raw::c_void;
 use std::ptr; // For ptr::null()
 
@@ -16,6 +16,15 @@ pub type OSStatus = i32;
 // https://developer.apple.com/documentation/coreaudio/audioobjectid?language=objc
 pub type AudioObjectID = u32;
 
+// CFBase.h / CFString.h
+// -------------------------
+// https://developer.apple.com/documentation/corefoundation/cfstringref?language=objc
+pub type CFStringRef = *const c_void;
+// https://developer.apple.com/documentation/corefoundation/cfstringencoding?language=objc
+type CFStringEncoding = u32;
+// https://developer.apple.com/documentation/corefoundation/1542581-cfstringbuiltinencodings/kcfstringencodingutf8
+pub const kCFStringEncodingUTF8: CFStringEncoding = 0x0800_0100;
+
 // https://developer.apple.com/documentation/coreaudio/audioobjectpropertyaddress/1422175-mselector?language=objc
 type AudioObjectPropertySelector = u32;
 // https://developer.apple.com/documentation/coreaudio/audioobjectpropertyscope?language=objc
@@ -25,6 +34,7 @@ type AudioObjectPropertyElement = u32;
 
 // https://developer.apple.com/documentation/coreaudio/audioobjectpropertyaddress?language=objc
 #[repr(C)] // Specify data layout in the same way as C does.
+#[derive(Clone, Copy)]
 pub struct AudioObjectPropertyAddress {
     pub mSelector: AudioObjectPropertySelector,
     pub mScope: AudioObjectPropertyScope,
@@ -32,14 +42,21 @@ pub struct AudioObjectPropertyAddress {
 }
 
 // https://developer.apple.com/documentation/coreaudio/1494531-anonymous/kaudiohardwarenoerror
-const kAudioHardwareNoError: OSStatus = 0;
+pub const kAudioHardwareNoError: OSStatus = 0;
 // https://developer.apple.com/documentation/coreaudio/1494531-anonymous/kaudiohardwarebadobjecterror
 // 0x'!obj' = 0x216F626A = 560947818
-#[cfg(test)]
 pub const kAudioHardwareBadObjectError: OSStatus = 560947818;
+// https://developer.apple.com/documentation/coreaudio/1494531-anonymous/kaudiohardwarenotrunningerror
+// 0x'stop' = 0x73746F70 = 1937010544
+pub const kAudioHardwareNotRunningError: OSStatus = 1937010544;
+// https://developer.apple.com/documentation/coreaudio/1494531-anonymous/kaudiohardwareunknownpropertyerror
+// 0x'who?' = 0x77686F3F = 2003332927
+pub const kAudioHardwareUnknownPropertyError: OSStatus = 2003332927;
+// https://developer.apple.com/documentation/coreaudio/1494531-anonymous/kaudiohardwarebaddeviceerror
+// 0x'!dev' = 0x21646576 = 560227702
+pub const kAudioHardwareBadDeviceError: OSStatus = 560227702;
 
 // https://developer.apple.com/documentation/coreaudio/1494461-anonymous/kaudioobjectunknown
-#[cfg(test)]
 pub const kAudioObjectUnknown: AudioObjectID = 0;
 
 // https://developer.apple.com/documentation/coreaudio/1494464-anonymous/kaudioobjectpropertyscopeglobal
@@ -59,6 +76,59 @@ pub const kAudioHardwarePropertyDefaultInputDevice: AudioObjectPropertySelector
 // https://developer.apple.com/documentation/coreaudio/1545886-anonymous/kaudiohardwarepropertydefaultoutputdevice
 // 0x'dOut' = 0x644F7574 = 1682929012
 pub const kAudioHardwarePropertyDefaultOutputDevice: AudioObjectPropertySelector = 1682929012;
+// https://developer.apple.com/documentation/coreaudio/1545894-anonymous/kaudiohardwarepropertydevices
+// 0x'dev#' = 0x64657623 = 1684370979
+pub const kAudioHardwarePropertyDevices: AudioObjectPropertySelector = 1684370979;
+
+// https://developer.apple.com/documentation/coreaudio/1494464-anonymous/kaudioobjectpropertyscopeinput
+// 0x'inpt' = 0x696E7074
+pub const kAudioObjectPropertyScopeInput: AudioObjectPropertyScope = 0x696E_7074;
+// https://developer.apple.com/documentation/coreaudio/1494464-anonymous/kaudioobjectpropertyscopeoutput
+// 0x'outp' = 0x6F757470
+pub const kAudioObjectPropertyScopeOutput: AudioObjectPropertyScope = 0x6F75_7470;
+
+// AudioDevice.h
+// -------------------------
+// https://developer.apple.com/documentation/coreaudio/1579935-anonymous/kaudiodevicepropertystreamconfiguration
+// 0x'slay' = 0x736C6179 = 1936482681
+pub const kAudioDevicePropertyStreamConfiguration: AudioObjectPropertySelector = 1936482681;
+// https://developer.apple.com/documentation/coreaudio/1619031-anonymous/kaudiodevicepropertydevicenamecfstring
+// 0x'lnam' = 0x6C6E616D = 1819173229
+pub const kAudioDevicePropertyDeviceNameCFString: AudioObjectPropertySelector = 1819173229;
+// https://developer.apple.com/documentation/coreaudio/1584097-anonymous/kaudiodevicepropertyavailablenominalsamplerates
+// 0x'nsr#' = 0x6E737223 = 1853059619
+pub const kAudioDevicePropertyAvailableNominalSampleRates: AudioObjectPropertySelector =
+    1853059619;
+
+// https://developer.apple.com/documentation/coreaudio/audiovaluerange?language=objc
+#[repr(C)]
+pub struct AudioValueRange {
+    pub mMinimum: f64,
+    pub mMaximum: f64,
+}
+
+// https://developer.apple.com/documentation/coreaudio/audiobuffer?language=objc
+#[repr(C)]
+pub struct AudioBuffer {
+    pub mNumberChannels: u32,
+    pub mDataByteSize: u32,
+    pub mData: *mut c_void,
+}
+
+// https://developer.apple.com/documentation/coreaudio/audiobufferlist?language=objc
+#[repr(C)]
+pub struct AudioBufferList {
+    pub mNumberBuffers: u32,
+    pub mBuffers: [AudioBuffer; 1], // The real array length is `mNumberBuffers`.
+}
+
+// https://developer.apple.com/documentation/coreaudio/audioobjectpropertylistenerproc?language=objc
+pub type AudioObjectPropertyListenerProc = extern "C" fn(
+    inObjectID: AudioObjectID,
+    inNumberAddresses: u32,
+    inAddresses: *const AudioObjectPropertyAddress,
+    inClientData: *mut c_void,
+) -> OSStatus;
 
 #[cfg(target_os = "macos")] // The function is only included on macOS.
 #[link(name = "CoreAudio", kind = "framework")] // Link dynamically to CoreAudio.
@@ -72,15 +142,57 @@ extern "C" {
         ioDataSize: *mut u32,
         outData: *mut c_void,
     ) -> OSStatus;
+
+    // https://developer.apple.com/documentation/coreaudio/1422450-audioobjectgetpropertydatasize?language=objc
+    fn AudioObjectGetPropertyDataSize(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inQualifierDataSize: u32,
+        inQualifierData: *const c_void,
+        outDataSize: *mut u32,
+    ) -> OSStatus;
+
+    // https://developer.apple.com/documentation/coreaudio/1422651-audioobjectaddpropertylistener?language=objc
+    fn AudioObjectAddPropertyListener(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inListener: AudioObjectPropertyListenerProc,
+        inClientData: *mut c_void,
+    ) -> OSStatus;
+
+    // https://developer.apple.com/documentation/coreaudio/1422170-audioobjectremovepropertylistener?language=objc
+    fn AudioObjectRemovePropertyListener(
+        inObjectID: AudioObjectID,
+        inAddress: *const AudioObjectPropertyAddress,
+        inListener: AudioObjectPropertyListenerProc,
+        inClientData: *mut c_void,
+    ) -> OSStatus;
+}
+
+#[cfg(target_os = "macos")] // The function is only included on macOS.
+#[link(name = "CoreFoundation", kind = "framework")] // Link dynamically to CoreFoundation.
+extern "C" {
+    // https://developer.apple.com/documentation/corefoundation/1542721-cfstringgetcstringptr?language=objc
+    fn CFStringGetCStringPtr(theString: CFStringRef, encoding: CFStringEncoding) -> *const i8;
+    // https://developer.apple.com/documentation/corefoundation/1542143-cfstringgetcstring?language=objc
+    fn CFStringGetCString(
+        theString: CFStringRef,
+        buffer: *mut i8,
+        bufferSize: isize,
+        encoding: CFStringEncoding,
+    ) -> u8;
+    // https://developer.apple.com/documentation/corefoundation/1521153-cfrelease?language=objc
+    fn CFRelease(cf: *const c_void);
 }
 
 pub fn get_property_data<T>(
     id: AudioObjectID,
     address: &AudioObjectPropertyAddress,
 ) -> Result<T, OSStatus> {
-    // Using `mem::uninitialized()` to bypasses memory-initialization checks.
-    let mut data: T = unsafe { mem::uninitialized() };
-    let mut size = mem::size_of_val(&data) as u32; // Cast usize to u32.
+    // Use `MaybeUninit` instead of `mem::uninitialized()`, which is UB for
+    // types with invalid bit patterns.
+    let mut data: mem::MaybeUninit<T> = mem::MaybeUninit::uninit();
+    let mut size = mem::size_of::<T>() as u32; // Cast usize to u32.
     let status: OSStatus = unsafe {
         AudioObjectGetPropertyData(
             id,
@@ -91,14 +203,64 @@ pub fn get_property_data<T>(
             ptr::null(),
             // Cast u32 ref to a raw u32 pointer.
             &mut size as *mut u32,
-            // Cast T ref to a raw T pointer first,
-            // and then cast raw T pointer to void pointer.
-            &mut data as *mut T as *mut c_void,
+            data.as_mut_ptr() as *mut c_void,
+        )
+    };
+    if status != kAudioHardwareNoError {
+        return Err(status);
+    }
+    if size as usize != mem::size_of::<T>() {
+        // CoreAudio reported success but wrote a different number of bytes
+        // than `T` expects; surface it as an error rather than risking UB.
+        return Err(-1);
+    }
+    // Safe: `status` confirmed success and the size check above confirmed an
+    // exact-size write, so `data` is fully initialized.
+    Ok(unsafe { data.assume_init() })
+}
+
+pub fn get_property_array<T>(
+    id: AudioObjectID,
+    address: &AudioObjectPropertyAddress,
+) -> Result<Vec<T>, OSStatus> {
+    let mut size: u32 = 0;
+    let status: OSStatus = unsafe {
+        AudioObjectGetPropertyDataSize(
+            id,
+            address as *const AudioObjectPropertyAddress,
+            0,
+            ptr::null(),
+            &mut size as *mut u32,
+        )
+    };
+    if status != kAudioHardwareNoError {
+        return Err(status);
+    }
+
+    let count = size as usize / mem::size_of::<T>();
+    let mut data: Vec<T> = Vec::with_capacity(count);
+    let status: OSStatus = unsafe {
+        AudioObjectGetPropertyData(
+            id,
+            address as *const AudioObjectPropertyAddress,
+            0,
+            ptr::null(),
+            &mut size as *mut u32,
+            data.as_mut_ptr() as *mut c_void,
         )
     };
-    if status == kAudioHardwareNoError {
-        Ok(data)
-    } else {
-        Err(status)
+    if status != kAudioHardwareNoError {
+        return Err(status);
+    }
+    // The second call can write fewer bytes than the size query reported
+    // (e.g. the array shrank in between). Any `size` other than exactly
+    // `count` elements is rejected as an error, so uninitialized elements of
+    // `data` are never marked as initialized.
+    if size as usize != count * mem::size_of::<T>() {
+        return Err(-1);
+    }
+    unsafe {
+        data.set_len(count);
     }
+    Ok(data)
 }
@@ -6,3 +6,11 @@ fn test_get_default_device_id() {
     assert!(utils::get_default_device_id(utils::Scope::Input).is_ok());
     assert!(utils::get_default_device_id(utils::Scope::Output).is_ok());
 }
+
+#[test]
+fn test_get_device_ids_c_abi() {
+    let mut ids: [i32; 16] = [0; 16];
+    let mut count: u32 = ids.len() as u32;
+    let error = rust_audio_lib::get_device_ids(utils::Scope::Output, ids.as_mut_ptr(), &mut count);
+    assert_eq!(error, utils::Error::Ok);
+}